@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! `Header::difficulty`, forwarded to `bits` so it picks up custom [`Params`] the same way
+//! `Target`/`CompactTarget` do.
+
+use crate::consensus::Params;
+
+impl Header {
+    /// Computes the difficulty of this header's `bits`, relative to `params`'s maximum
+    /// attainable target.
+    ///
+    /// Accepts `impl AsRef<Params>` so callers can pass either a [`Network`] or a fully
+    /// specified [`Params`] for a custom chain.
+    pub fn difficulty(&self, params: impl AsRef<Params>) -> u128 { self.bits.difficulty(params) }
+
+    /// Computes the difficulty of this header's `bits` as a `f64`, relative to
+    /// `Network::Bitcoin`'s maximum attainable target.
+    pub fn difficulty_float(&self) -> f64 { self.bits.difficulty_float() }
+}
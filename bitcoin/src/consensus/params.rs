@@ -6,10 +6,10 @@
 //! networks (such as mainnet, testnet, testnet4).
 //!
 
+use core::fmt;
+
 use crate::network::Network;
-#[cfg(doc)]
-use crate::pow::CompactTarget;
-use crate::pow::Target;
+use crate::pow::{CompactTarget, Target};
 
 /// Parameters that influence chain consensus.
 #[non_exhaustive]
@@ -199,6 +199,63 @@ impl Params {
     pub fn difficulty_adjustment_interval(&self) -> u64 {
         self.pow_target_timespan / self.pow_target_spacing
     }
+
+    /// Computes the next proof-of-work target following the standard retargeting algorithm.
+    ///
+    /// `first_block_time` and `last_block_time` are the block times of the first and last
+    /// blocks in the retarget period that just elapsed, and `last_target` is the target that
+    /// was in effect over that period.
+    ///
+    /// If [`Params::no_pow_retargeting`] is set the target is left unchanged, as is the case on
+    /// `regtest`.
+    ///
+    /// `last_target * actual_timespan` is computed before dividing by `pow_target_timespan`. The
+    /// bounds enforced by [`ParamsBuilder::build`] (a timespan of at most one year and a
+    /// `max_attainable_target` of at most a quarter of `Target::MAX`) do not rule out that product
+    /// overflowing `Target`'s wide-integer representation for a `last_target` near that cap.
+    pub fn next_work_required(
+        &self,
+        last_target: Target,
+        first_block_time: u32,
+        last_block_time: u32,
+    ) -> CompactTarget {
+        if self.no_pow_retargeting {
+            return last_target.to_compact_lossy();
+        }
+
+        let min_timespan = self.pow_target_timespan / 4;
+        let max_timespan = self.pow_target_timespan.saturating_mul(4);
+
+        let actual_timespan = last_block_time.saturating_sub(first_block_time) as u64;
+        let actual_timespan = actual_timespan.clamp(min_timespan, max_timespan);
+
+        let new_target = (last_target * actual_timespan) / self.pow_target_timespan;
+        let new_target =
+            if new_target > self.max_attainable_target { self.max_attainable_target } else { new_target };
+
+        new_target.to_compact_lossy()
+    }
+
+    /// Returns the maximum target reachable from `current` in a single retarget step, i.e.
+    /// `current * 4`, without clamping to [`Params::max_attainable_target`].
+    ///
+    /// Prefer [`Params::max_transition_threshold`] unless the unclamped value is specifically
+    /// needed.
+    pub fn max_transition_threshold_unchecked(&self, current: Target) -> Target { current * 4 }
+
+    /// Returns the maximum target reachable from `current` in a single retarget step, clamped to
+    /// this network's [`Params::max_attainable_target`].
+    ///
+    /// Useful for rejecting headers whose target increased by more than 4x relative to the
+    /// previous retarget period.
+    pub fn max_transition_threshold(&self, current: Target) -> Target {
+        let max = self.max_transition_threshold_unchecked(current);
+        if max > self.max_attainable_target { self.max_attainable_target } else { max }
+    }
+
+    /// Returns a [`ParamsBuilder`] seeded with `network`'s parameters, for tuning block times and
+    /// difficulty rules on a private chain or custom signet.
+    pub fn builder(network: Network) -> ParamsBuilder { ParamsBuilder::new(network) }
 }
 
 impl From<Network> for Params {
@@ -224,3 +281,416 @@ impl AsRef<Params> for Params {
 impl AsRef<Params> for Network {
     fn as_ref(&self) -> &Params { Self::params(*self) }
 }
+
+/// A BIP9 soft-fork deployment, identified by the version bit it signals on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deployment {
+    /// The bit in the block version field used to signal readiness for this deployment.
+    ///
+    /// Valid values are `0..=28`; BIP9 reserves the top three bits of the version field (see
+    /// `VERSION_BITS_TOP_MASK` below), so a `bit` outside that range can never signal and
+    /// [`Deployment::signals`] treats it as such rather than panicking.
+    pub bit: u8,
+    /// Median time past at or after which blocks may start signaling for this deployment.
+    pub start_time: u32,
+    /// Median time past at or after which the deployment is considered failed if it has not
+    /// already locked in.
+    pub timeout: u32,
+}
+
+/// The BIP9 state of a [`Deployment`] at a particular block height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdState {
+    /// `start_time` has not yet been reached.
+    Defined,
+    /// `start_time` has been reached; blocks may signal readiness.
+    Started,
+    /// Enough blocks signaled within a single confirmation window; activation is locked in.
+    LockedIn,
+    /// The deployment is active and its rules are enforced.
+    Active,
+    /// `timeout` was reached before the activation threshold, so the deployment will never
+    /// activate.
+    Failed,
+}
+
+// Top three bits of the block version must be 001 to signal BIP9 versionbits (BIP9 §Reserved
+// Bits and Version Bits Usage).
+const VERSION_BITS_TOP_MASK: i32 = 0xe000_0000u32 as i32;
+const VERSION_BITS_TOP_BITS: i32 = 0x2000_0000;
+
+impl Deployment {
+    /// Returns whether `version` signals readiness for this deployment's bit.
+    ///
+    /// Returns `false`, rather than panicking or matching on the reserved top bits, for a `bit`
+    /// outside BIP9's valid `0..=28` range.
+    fn signals(&self, version: i32) -> bool {
+        if self.bit > 28 {
+            return false;
+        }
+        let mask = 1i32 << self.bit;
+        (version & VERSION_BITS_TOP_MASK) == VERSION_BITS_TOP_BITS && (version & mask) != 0
+    }
+
+    /// Computes this deployment's [`ThresholdState`] at `height`.
+    ///
+    /// `history` holds one `(median_time_past, version)` entry per block, oldest first,
+    /// starting at height 0, and must cover every retarget boundary up to `height`. As per
+    /// BIP9, state transitions only happen at retarget boundaries, i.e. where
+    /// `height % params.miner_confirmation_window == 0`.
+    pub fn state_at(&self, params: &Params, height: u32, history: &[(u32, i32)]) -> ThresholdState {
+        let window = params.miner_confirmation_window as usize;
+        if window == 0 {
+            return ThresholdState::Defined;
+        }
+
+        let mut state = ThresholdState::Defined;
+        let periods = height as usize / window;
+
+        for period in 0..periods {
+            let start = period * window;
+            let end = start + window;
+            let period_blocks = match history.get(start..end) {
+                Some(blocks) => blocks,
+                None => break,
+            };
+            let median_time_past = period_blocks[period_blocks.len() - 1].0;
+
+            state = match state {
+                ThresholdState::Defined if median_time_past >= self.start_time => ThresholdState::Started,
+                ThresholdState::Defined => ThresholdState::Defined,
+                ThresholdState::Started if median_time_past >= self.timeout => ThresholdState::Failed,
+                ThresholdState::Started => {
+                    let signaling =
+                        period_blocks.iter().filter(|&&(_, version)| self.signals(version)).count() as u32;
+                    if signaling >= params.rule_change_activation_threshold {
+                        ThresholdState::LockedIn
+                    } else {
+                        ThresholdState::Started
+                    }
+                }
+                ThresholdState::LockedIn => ThresholdState::Active,
+                ThresholdState::Active => ThresholdState::Active,
+                ThresholdState::Failed => ThresholdState::Failed,
+            };
+        }
+
+        state
+    }
+}
+
+/// The largest `pow_target_timespan` [`ParamsBuilder::build`] will accept.
+///
+/// Generously above every built-in network's timespan (testnet4's 2 weeks is the longest), this
+/// bounds the multiplier `actual_timespan` in [`Params::next_work_required`] to at most four
+/// times this value.
+const MAX_POW_TARGET_TIMESPAN: u64 = 365 * 24 * 60 * 60; // 1 year.
+
+/// The largest `max_attainable_target` [`ParamsBuilder::build`] will accept.
+///
+/// Capping custom networks to a quarter of [`Target::MAX`] guarantees that `current * 4` in
+/// [`Params::max_transition_threshold_unchecked`] cannot overflow `Target`'s representation for
+/// any `current` up to `max_attainable_target`, while still comfortably accommodating every
+/// network this crate ships (all of which are, by definition, attainable and therefore well below
+/// `Target::MAX`). It does not bound `last_target * actual_timespan` in
+/// [`Params::next_work_required`], which can overflow for a `last_target` near this cap.
+fn max_safe_attainable_target() -> Target { Target::MAX / 4 }
+
+/// A builder for [`Params`], for configuring custom or private networks.
+///
+/// Construct one with [`Params::builder`], seeded from a known network's parameters, then
+/// override whichever fields need tuning before calling [`ParamsBuilder::build`].
+#[derive(Debug, Clone)]
+pub struct ParamsBuilder {
+    params: Params,
+}
+
+impl ParamsBuilder {
+    /// Creates a new builder seeded with `network`'s parameters.
+    fn new(network: Network) -> Self { ParamsBuilder { params: Params::new(network) } }
+
+    /// Sets the time when BIP16 becomes active.
+    pub fn bip16_time(mut self, value: u32) -> Self {
+        self.params.bip16_time = value;
+        self
+    }
+
+    /// Sets the block height at which BIP34 becomes active.
+    pub fn bip34_height(mut self, value: u32) -> Self {
+        self.params.bip34_height = value;
+        self
+    }
+
+    /// Sets the block height at which BIP65 becomes active.
+    pub fn bip65_height(mut self, value: u32) -> Self {
+        self.params.bip65_height = value;
+        self
+    }
+
+    /// Sets the block height at which BIP66 becomes active.
+    pub fn bip66_height(mut self, value: u32) -> Self {
+        self.params.bip66_height = value;
+        self
+    }
+
+    /// Sets the minimum number of blocks signaling within `miner_confirmation_window` required
+    /// for a BIP9 deployment to lock in.
+    pub fn rule_change_activation_threshold(mut self, value: u32) -> Self {
+        self.params.rule_change_activation_threshold = value;
+        self
+    }
+
+    /// Sets the number of blocks with the same set of rules.
+    pub fn miner_confirmation_window(mut self, value: u32) -> Self {
+        self.params.miner_confirmation_window = value;
+        self
+    }
+
+    /// Sets the maximum attainable target value for these params.
+    pub fn max_attainable_target(mut self, value: Target) -> Self {
+        #[allow(deprecated)]
+        {
+            self.params.pow_limit = value;
+        }
+        self.params.max_attainable_target = value;
+        self
+    }
+
+    /// Sets the expected amount of time to mine one block.
+    pub fn pow_target_spacing(mut self, value: u64) -> Self {
+        self.params.pow_target_spacing = value;
+        self
+    }
+
+    /// Sets the difficulty recalculation interval.
+    pub fn pow_target_timespan(mut self, value: u64) -> Self {
+        self.params.pow_target_timespan = value;
+        self
+    }
+
+    /// Sets whether minimal difficulty may be used for blocks or not.
+    pub fn allow_min_difficulty_blocks(mut self, value: bool) -> Self {
+        self.params.allow_min_difficulty_blocks = value;
+        self
+    }
+
+    /// Sets whether retargeting is disabled for this network or not.
+    pub fn no_pow_retargeting(mut self, value: bool) -> Self {
+        self.params.no_pow_retargeting = value;
+        self
+    }
+
+    /// Validates and returns the configured [`Params`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuilderError::ZeroTargetSpacing`] if `pow_target_spacing` is zero. Returns
+    /// [`BuilderError::TargetTimespanOutOfRange`] if `pow_target_timespan` is zero or larger than
+    /// [`MAX_POW_TARGET_TIMESPAN`]. Returns [`BuilderError::ZeroDifficultyAdjustmentInterval`] if
+    /// `pow_target_timespan < pow_target_spacing`, which would make
+    /// [`Params::difficulty_adjustment_interval`] zero. Returns
+    /// [`BuilderError::MaxAttainableTargetTooLarge`] if `max_attainable_target` is larger than a
+    /// quarter of `Target::MAX`. Together these checks rule out a division by zero in
+    /// [`Params::difficulty_adjustment_interval`] and [`Params::next_work_required`], and an
+    /// overflow of [`Params::max_transition_threshold_unchecked`] for targets up to
+    /// `max_attainable_target`; they do not rule out an overflow of `last_target * actual_timespan`
+    /// in [`Params::next_work_required`].
+    pub fn build(self) -> Result<Params, BuilderError> {
+        if self.params.pow_target_spacing == 0 {
+            return Err(BuilderError::ZeroTargetSpacing);
+        }
+        if self.params.pow_target_timespan == 0 || self.params.pow_target_timespan > MAX_POW_TARGET_TIMESPAN {
+            return Err(BuilderError::TargetTimespanOutOfRange);
+        }
+        if self.params.difficulty_adjustment_interval() == 0 {
+            return Err(BuilderError::ZeroDifficultyAdjustmentInterval);
+        }
+        if self.params.max_attainable_target > max_safe_attainable_target() {
+            return Err(BuilderError::MaxAttainableTargetTooLarge);
+        }
+
+        Ok(self.params)
+    }
+}
+
+/// An error returned by [`ParamsBuilder::build`] when the configured parameters are invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuilderError {
+    /// `pow_target_spacing` was zero.
+    ZeroTargetSpacing,
+    /// `pow_target_timespan` was zero or greater than [`MAX_POW_TARGET_TIMESPAN`].
+    TargetTimespanOutOfRange,
+    /// `pow_target_timespan` was smaller than `pow_target_spacing`, making
+    /// [`Params::difficulty_adjustment_interval`] zero.
+    ZeroDifficultyAdjustmentInterval,
+    /// `max_attainable_target` was larger than a quarter of `Target::MAX`.
+    MaxAttainableTargetTooLarge,
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuilderError::ZeroTargetSpacing =>
+                write!(f, "pow_target_spacing must be non-zero"),
+            BuilderError::TargetTimespanOutOfRange => write!(
+                f,
+                "pow_target_timespan must be non-zero and at most {}",
+                MAX_POW_TARGET_TIMESPAN
+            ),
+            BuilderError::ZeroDifficultyAdjustmentInterval =>
+                write!(f, "pow_target_timespan must be at least pow_target_spacing"),
+            BuilderError::MaxAttainableTargetTooLarge =>
+                write!(f, "max_attainable_target must not exceed a quarter of Target::MAX"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuilderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_params(pow_target_timespan: u64) -> Params {
+        Params::builder(Network::Regtest)
+            .no_pow_retargeting(false)
+            .pow_target_timespan(pow_target_timespan)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn next_work_required_no_retargeting_keeps_target() {
+        let params = Params::REGTEST; // no_pow_retargeting is true.
+        let target = Target::from_compact(CompactTarget::from_consensus(0x1f00_ffff));
+
+        let got = params.next_work_required(target, 0, 1_000_000);
+
+        assert_eq!(got, target.to_compact_lossy());
+    }
+
+    #[test]
+    fn next_work_required_clamps_actual_timespan_to_four_times() {
+        let params = custom_params(600);
+        // Low-difficulty target, well under a quarter of max_attainable_target, so the result
+        // is not itself clamped by max_attainable_target.
+        let target = Target::from_compact(CompactTarget::from_consensus(0x1f00_ffff));
+
+        // Elapsed time is far more than 4x the timespan, so it must be clamped down to 4x.
+        let got = params.next_work_required(target, 0, 100 * params.pow_target_timespan as u32);
+        let want = (target * 4).to_compact_lossy();
+
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn next_work_required_clamps_actual_timespan_to_one_quarter() {
+        let params = custom_params(600);
+        let target = Target::from_compact(CompactTarget::from_consensus(0x1f00_ffff));
+
+        // Elapsed time is far less than a quarter of the timespan, so it must be clamped up to
+        // a quarter.
+        let got = params.next_work_required(target, 0, 1);
+        let want = (target / 4).to_compact_lossy();
+
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn max_transition_threshold_unchecked_is_four_times_current() {
+        let params = Params::REGTEST;
+        let current = Target::from_compact(CompactTarget::from_consensus(0x1f00_ffff));
+
+        assert_eq!(params.max_transition_threshold_unchecked(current), current * 4);
+    }
+
+    #[test]
+    fn max_transition_threshold_clamps_to_max_attainable_target() {
+        let params = Params::REGTEST;
+        // Close enough to max_attainable_target that *4 would overshoot it.
+        let current = params.max_attainable_target / 2;
+
+        assert_eq!(params.max_transition_threshold(current), params.max_attainable_target);
+    }
+
+    #[test]
+    fn max_transition_threshold_passes_through_below_max_attainable_target() {
+        let params = Params::REGTEST;
+        let current = Target::from_compact(CompactTarget::from_consensus(0x1f00_ffff));
+
+        assert_eq!(params.max_transition_threshold(current), current * 4);
+    }
+
+    // REGTEST has a 3-block confirmation window and a threshold of 1, making the state
+    // machine exercisable with a handful of blocks.
+    const SIGNALING_VERSION: i32 = 0x2000_0001_u32 as i32; // Top bits set, signals bit 0.
+    const NOT_SIGNALING_VERSION: i32 = 0x2000_0000_u32 as i32; // Top bits set, no bits signaled.
+
+    fn test_deployment() -> Deployment { Deployment { bit: 0, start_time: 100, timeout: 1_000 } }
+
+    #[test]
+    fn deployment_state_at_walks_defined_started_locked_in_active() {
+        let params = Params::REGTEST;
+        let deployment = test_deployment();
+
+        // Period 0: median_time_past below start_time, so still Defined.
+        let mut history = vec![(50, NOT_SIGNALING_VERSION); 3];
+        assert_eq!(deployment.state_at(&params, 3, &history), ThresholdState::Defined);
+
+        // Period 1: median_time_past reaches start_time, so Defined -> Started.
+        history.extend(vec![(150, NOT_SIGNALING_VERSION); 3]);
+        assert_eq!(deployment.state_at(&params, 6, &history), ThresholdState::Started);
+
+        // Period 2: now Started, and REGTEST's threshold of 1 is met by a single signaling
+        // block within the window, so Started -> LockedIn.
+        history.extend(vec![(200, SIGNALING_VERSION); 3]);
+        assert_eq!(deployment.state_at(&params, 9, &history), ThresholdState::LockedIn);
+
+        // Period 3: one more window after locking in, LockedIn -> Active.
+        history.extend(vec![(250, NOT_SIGNALING_VERSION); 3]);
+        assert_eq!(deployment.state_at(&params, 12, &history), ThresholdState::Active);
+    }
+
+    #[test]
+    fn deployment_state_at_started_times_out_to_failed() {
+        let params = Params::REGTEST;
+        let deployment = test_deployment();
+
+        // Period 0: Defined -> Started.
+        let mut history = vec![(150, NOT_SIGNALING_VERSION); 3];
+        // Period 1: median_time_past reaches timeout with no blocks signaling, so Failed.
+        history.extend(vec![(1_000, NOT_SIGNALING_VERSION); 3]);
+
+        assert_eq!(deployment.state_at(&params, 6, &history), ThresholdState::Failed);
+    }
+
+    #[test]
+    fn builder_rejects_zero_target_spacing() {
+        let result = Params::builder(Network::Regtest).pow_target_spacing(0).build();
+        assert_eq!(result.unwrap_err(), BuilderError::ZeroTargetSpacing);
+    }
+
+    #[test]
+    fn builder_rejects_out_of_range_target_timespan() {
+        let result = Params::builder(Network::Regtest).pow_target_timespan(0).build();
+        assert_eq!(result.unwrap_err(), BuilderError::TargetTimespanOutOfRange);
+
+        let result =
+            Params::builder(Network::Regtest).pow_target_timespan(MAX_POW_TARGET_TIMESPAN + 1).build();
+        assert_eq!(result.unwrap_err(), BuilderError::TargetTimespanOutOfRange);
+    }
+
+    #[test]
+    fn builder_accepts_sane_params() {
+        let params = Params::builder(Network::Regtest)
+            .pow_target_spacing(30)
+            .pow_target_timespan(MAX_POW_TARGET_TIMESPAN)
+            .build()
+            .unwrap();
+
+        assert_eq!(params.pow_target_spacing, 30);
+        assert_eq!(params.pow_target_timespan, MAX_POW_TARGET_TIMESPAN);
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: CC0-1.0
+
+//! Proof-of-work related difficulty measures.
+//!
+//! `Target::difficulty` and `CompactTarget::difficulty` take `impl AsRef<Params>` rather than a
+//! `Network`, so a caller passing a custom [`Params`] (e.g. from `Params::builder`) gets back the
+//! right number for their chain instead of whatever mainnet's limit happens to be.
+
+use crate::consensus::Params;
+
+impl Target {
+    /// Computes the difficulty for this `Target` relative to `params`'s maximum attainable
+    /// target.
+    ///
+    /// Accepting `impl AsRef<Params>` lets callers pass either a [`Network`] or a fully
+    /// specified [`Params`].
+    ///
+    /// Saturates at `u128::MAX` rather than panicking if the ratio does not fit, which can
+    /// happen for a custom [`Params`] paired with a very small `Target`.
+    pub fn difficulty(&self, params: impl AsRef<Params>) -> u128 {
+        debug_assert_ne!(self.0, Self::ZERO.0, "difficulty is undefined for a target of 0");
+        let max_attainable = params.as_ref().max_attainable_target;
+        (max_attainable.0 / self.0).to_u128().unwrap_or(u128::MAX)
+    }
+
+    /// Computes the difficulty for this `Target` as a `f64`, relative to `Network::Bitcoin`'s
+    /// maximum attainable target.
+    pub fn difficulty_float(&self) -> f64 {
+        debug_assert_ne!(self.0, Self::ZERO.0, "difficulty is undefined for a target of 0");
+        Self::MAX_ATTAINABLE_MAINNET.0.to_f64() / self.0.to_f64()
+    }
+}
+
+impl CompactTarget {
+    /// Computes the difficulty for this `CompactTarget` relative to `params`'s maximum
+    /// attainable target.
+    pub fn difficulty(&self, params: impl AsRef<Params>) -> u128 {
+        Target::from(*self).difficulty(params)
+    }
+
+    /// Computes the difficulty for this `CompactTarget` as a `f64`, relative to
+    /// `Network::Bitcoin`'s maximum attainable target.
+    pub fn difficulty_float(&self) -> f64 { Target::from(*self).difficulty_float() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_of_max_attainable_target_is_one() {
+        let target = Target::MAX_ATTAINABLE_MAINNET;
+        assert_eq!(target.difficulty(&Params::MAINNET), 1);
+    }
+
+    #[test]
+    fn difficulty_doubles_as_target_halves() {
+        let target = Target::MAX_ATTAINABLE_MAINNET / 2;
+        assert_eq!(target.difficulty(&Params::MAINNET), 2);
+    }
+
+    #[test]
+    fn compact_target_difficulty_matches_target_difficulty() {
+        let compact = CompactTarget::from_consensus(0x1d00_ffff);
+        let target = Target::from_compact(compact);
+
+        assert_eq!(compact.difficulty(&Params::MAINNET), target.difficulty(&Params::MAINNET));
+        assert_eq!(compact.difficulty_float(), target.difficulty_float());
+    }
+
+    #[test]
+    fn difficulty_saturates_instead_of_panicking() {
+        let params = Params::builder(crate::network::Network::Regtest)
+            .max_attainable_target(Target::MAX_ATTAINABLE_REGTEST)
+            .build()
+            .unwrap();
+        // Exponent 3 with mantissa 1 decodes to a target of exactly 1, not 0, so this
+        // exercises the to_u128 overflow-saturation path rather than a divide-by-zero.
+        let target = Target::from_compact(CompactTarget::from_consensus(0x0300_0001));
+
+        assert_eq!(target.difficulty(&params), u128::MAX);
+    }
+}